@@ -30,46 +30,105 @@
 //!
 //! You may still use this in the first two cases, but do be aware that you are wasting performance and memory.
 //! This can be worthwhile for consistency sometimes.
+//!
+//! ##### Key Generation
+//! By default, `add` draws keys from a [`SmallRng`](rand::rngs::SmallRng) seeded once per
+//! `Table` instead of fetching the thread-local RNG on every call. Plug in your own
+//! [`KeyGen`] (e.g. via [`Table::with_key_gen`]) for deterministic key streams in tests and
+//! simulations, or for counter-based schemes where collisions aren't a concern.
 use fxhash::FxHashMap;
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+mod sharded;
+pub use sharded::{ShardedRef, ShardedRefMut, ShardedTable};
 
 // TODO evaluate performance degradation due to cpu cache misses when doing join_benchmark with random insertion order.
 // TODO reword readme.
 // TODO bump to 1.0.0 once I'm done with the other todos + have used this in other projects.
 
+/// A source of keys for [`Table::add`]. See the crate level documentation.
+pub trait KeyGen {
+    /// Produce the next key to hand out.
+    fn next_key(&mut self) -> u128;
+}
+
+/// The default [`KeyGen`]: a [`SmallRng`] seeded once when the table is created, rather
+/// than fetching the thread-local RNG on every [`Table::add`] call.
+#[derive(Debug)]
+pub struct DefaultKeyGen(SmallRng);
+
+impl Default for DefaultKeyGen {
+    fn default() -> Self {
+        Self(SmallRng::from_rng(&mut rand::rng()))
+    }
+}
+
+impl Clone for DefaultKeyGen {
+    /// Re-seeds from the thread RNG rather than copying the generator's state, so a
+    /// cloned table's keys don't collide with the original's on subsequent `add` calls.
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl KeyGen for DefaultKeyGen {
+    fn next_key(&mut self) -> u128 {
+        self.0.random()
+    }
+}
+
 /// See crate level documentation.
 #[derive(Debug, Clone)]
-pub struct Table<T> {
+pub struct Table<T, G = DefaultKeyGen> {
     // Contains a map of uuid to data vector index.
     map: FxHashMap<u128, usize>,
     data: Vec<T>,
     reverse: Vec<u128>,
+    key_gen: G,
 }
 
-impl<T> Default for Table<T> {
+impl<T, G: Default> Default for Table<T, G> {
     fn default() -> Self {
         Self::with_capacity(32)
     }
 }
 
-impl<T> Table<T> {
-    /// Add a new value with random key.
+impl<T, G: KeyGen> Table<T, G> {
+    /// Add a new value with a key drawn from this table's [`KeyGen`].
     /// This is what you want to use 95% of the time.
     pub fn add(&mut self, value: T) -> u128 {
-        let key = rand::rng().random();
+        let key = self.key_gen.next_key();
         self.add_with_key(key, value);
         key
     }
+}
 
+impl<T, G> Table<T, G> {
     /// Add a new value with manual key. Usually used during deserialization.
     /// Might be used for performance reasons when using a Table as a Map.
     /// For example, a map KeyCode -> GameEvent.
+    ///
+    /// If the key already exists, the value is updated in place instead of being
+    /// removed and re-pushed.
     pub fn add_with_key(&mut self, key: u128, value: T) {
-        self.remove(key);
-        self.data.push(value);
-        self.reverse.push(key);
-        let index = self.data.len() - 1;
-        self.map.insert(key, index);
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => *entry.get_mut() = value,
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+
+    /// Get the given key's entry in the table for in-place manipulation.
+    ///
+    /// This resolves the key's position with a single map probe, which `get`/`add_with_key`
+    /// used together would otherwise do twice.
+    pub fn entry(&mut self, key: u128) -> Entry<'_, T, G> {
+        match self.map.get(&key) {
+            Some(&index) => Entry::Occupied(OccupiedEntry { table: self, index }),
+            None => Entry::Vacant(VacantEntry { table: self, key }),
+        }
     }
 
     /// Get a value by key.
@@ -92,22 +151,34 @@ impl<T> Table<T> {
         }
     }
 
+    /// Resolve `key`'s dense index without borrowing the value.
+    ///
+    /// Exposed crate-internally so callers that hold a lock/guard across multiple
+    /// accesses (like `ShardedTable`) can resolve the index once and then read the value
+    /// through [`Table::get_by_index`] instead of re-hashing `key` on every access.
+    pub(crate) fn index_of(&self, key: u128) -> Option<usize> {
+        self.map.get(&key).copied()
+    }
+
     /// Remove an element using it's key.
     pub fn remove(&mut self, key: u128) -> Option<T> {
-        if let Some(index) = self.map.remove(&key) {
-            // Swap-remove from both data and reverse
-            let value = self.data.swap_remove(index);
-            // key that got moved to index
-            let pre_move_index = self.reverse[self.reverse.len() - 1];
-            self.reverse.swap_remove(index);
-
-            // if what we removed was not the last element, update the index
-            if index < self.reverse.len() {
-                *self.map.get_mut(&pre_move_index).unwrap() = index;
-            }
-            return Some(value);
+        let index = self.map.remove(&key)?;
+        Some(self.remove_at(index))
+    }
+
+    /// Swap-remove the element at `index` from `data`/`reverse` and fix up the map entry
+    /// of whatever element got moved into `index`. Assumes `index` has already been removed
+    /// from `map` by the caller.
+    fn remove_at(&mut self, index: usize) -> T {
+        let value = self.data.swap_remove(index);
+        self.reverse.swap_remove(index);
+
+        // if what we removed was not the last element, the last element got moved into
+        // `index`; fix up its map entry to point at its new position.
+        if let Some(&moved_key) = self.reverse.get(index) {
+            *self.map.get_mut(&moved_key).unwrap() = index;
         }
-        None
+        value
     }
 
     /// Get an iterator over the contained values.
@@ -115,17 +186,59 @@ impl<T> Table<T> {
         self.data.iter()
     }
 
+    /// Get a mutable iterator over the contained values.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.data.iter_mut()
+    }
+
+    /// Get an iterator over `(key, value)` pairs.
+    ///
+    /// This is O(1) per element and does no hashing: `reverse[i]` is exactly the key for
+    /// `data[i]`, so the two vecs are simply zipped together.
+    pub fn iter(&self) -> impl Iterator<Item = (u128, &T)> {
+        self.reverse.iter().copied().zip(self.data.iter())
+    }
+
+    /// Get a mutable iterator over `(key, value)` pairs. See [`Table::iter`].
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u128, &mut T)> {
+        self.reverse.iter().copied().zip(self.data.iter_mut())
+    }
+
     /// Return an iterator over keys.
-    pub fn keys(&self) -> std::collections::hash_map::Keys<u128, usize> {
+    pub fn keys(&self) -> std::collections::hash_map::Keys<'_, u128, usize> {
         self.map.keys()
     }
 
-    /// Creates a Table with a specific initial capacity.
-    pub fn with_capacity(capacity: usize) -> Self {
+    /// Get the `(key, value)` pair at a dense `index`, i.e. the `index`-th element
+    /// yielded by [`Table::values`] or [`Table::iter`].
+    ///
+    /// Indices are invalidated by `remove`, since it swap-removes the last element into
+    /// the removed slot.
+    pub fn get_by_index(&self, index: usize) -> Option<(u128, &T)> {
+        Some((*self.reverse.get(index)?, self.data.get(index)?))
+    }
+
+    /// Get the `(key, value)` pair at a dense `index`, with a mutable reference to the
+    /// value. See [`Table::get_by_index`].
+    pub fn get_by_index_mut(&mut self, index: usize) -> Option<(u128, &mut T)> {
+        Some((*self.reverse.get(index)?, self.data.get_mut(index)?))
+    }
+
+    /// Get the key stored at a dense `index`. See [`Table::get_by_index`].
+    pub fn key_at(&self, index: usize) -> Option<u128> {
+        self.reverse.get(index).copied()
+    }
+
+    /// Creates a Table with a specific initial capacity and key generator.
+    ///
+    /// Use this to plug in a custom [`KeyGen`], e.g. a seeded generator for deterministic
+    /// key streams in tests and simulations.
+    pub fn with_key_gen(capacity: usize, key_gen: G) -> Self {
         Self {
             map: FxHashMap::<u128, usize>::with_capacity_and_hasher(capacity, Default::default()),
             data: Vec::<T>::with_capacity(capacity),
             reverse: Vec::<u128>::with_capacity(capacity),
+            key_gen,
         }
     }
 
@@ -140,6 +253,207 @@ impl<T> Table<T> {
         self.reverse.clear();
         self.map.clear();
     }
+
+    /// Keep only the elements for which `f` returns `true`, removing the rest.
+    ///
+    /// This walks `data`/`reverse` once and rebuilds `map` once at the end, which is far
+    /// cheaper than calling `remove` per dropped element since that re-hashes and fixes up
+    /// `map` on every single removal.
+    pub fn retain(&mut self, mut f: impl FnMut(u128, &mut T) -> bool) {
+        let mut index = 0;
+        while index < self.data.len() {
+            let key = self.reverse[index];
+            if f(key, &mut self.data[index]) {
+                index += 1;
+            } else {
+                self.data.swap_remove(index);
+                self.reverse.swap_remove(index);
+            }
+        }
+
+        self.map.clear();
+        for (index, &key) in self.reverse.iter().enumerate() {
+            self.map.insert(key, index);
+        }
+    }
+
+    /// Remove and return every entry as an iterator of `(key, value)` pairs, emptying the table.
+    pub fn drain(&mut self) -> impl Iterator<Item = (u128, T)> + '_ {
+        self.map.clear();
+        self.reverse.drain(..).zip(self.data.drain(..))
+    }
+
+    /// Iterate this table's values, following each one's foreign key (computed by `key_of`)
+    /// into `other`, yielding only the pairs where that key exists there.
+    ///
+    /// This expresses the component-linking pattern seen in `join_benchmark` and
+    /// `ecs_like_benchmark` without resorting to the `unsafe { get_mut(...).unwrap_unchecked() }`
+    /// those benchmarks use for speed.
+    pub fn join_with<'a, U, GU>(
+        &'a self,
+        other: &'a Table<U, GU>,
+        key_of: impl Fn(&T) -> u128 + 'a,
+    ) -> impl Iterator<Item = (&'a T, &'a U)> + 'a {
+        self.data
+            .iter()
+            .filter_map(move |value| other.get(key_of(value)).map(|other_value| (value, other_value)))
+    }
+
+    /// Like [`Table::join_with`], but visits each matching pair through `f` instead of
+    /// returning an iterator.
+    ///
+    /// A callback is used rather than an iterator of `(&T, &mut U)` so that no `&mut` into
+    /// `other` can escape and outlive a later match — an iterator handing out per-element
+    /// `&mut`s into the same `other` on every call would let a caller collect them and hold
+    /// overlapping mutable borrows of `other` at once, which is unsound.
+    pub fn join_with_mut<U, GU>(
+        &self,
+        other: &mut Table<U, GU>,
+        key_of: impl Fn(&T) -> u128,
+        mut f: impl FnMut(&T, &mut U),
+    ) {
+        for value in self.data.iter() {
+            if let Some(other_value) = other.get_mut(key_of(value)) {
+                f(value, other_value);
+            }
+        }
+    }
+}
+
+impl<T, G: Default> Table<T, G> {
+    /// Creates a Table with a specific initial capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_key_gen(capacity, G::default())
+    }
+}
+
+/// A view into a single key's slot in a [`Table`], obtained via [`Table::entry`].
+pub enum Entry<'a, T, G = DefaultKeyGen> {
+    /// The key is present in the table.
+    Occupied(OccupiedEntry<'a, T, G>),
+    /// The key is absent from the table.
+    Vacant(VacantEntry<'a, T, G>),
+}
+
+impl<'a, T, G> Entry<'a, T, G> {
+    /// Insert `value` if the entry is vacant, then return a mutable reference to the value.
+    pub fn or_insert(self, value: T) -> &'a mut T {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(value),
+        }
+    }
+
+    /// Insert the value produced by `f` if the entry is vacant, then return a mutable
+    /// reference to the value.
+    pub fn or_insert_with(self, f: impl FnOnce() -> T) -> &'a mut T {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Run `f` against the value if the entry is occupied, then return the entry unchanged.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut T)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// An occupied entry. See [`Table::entry`].
+pub struct OccupiedEntry<'a, T, G = DefaultKeyGen> {
+    table: &'a mut Table<T, G>,
+    index: usize,
+}
+
+impl<'a, T, G> OccupiedEntry<'a, T, G> {
+    /// Get a reference to the existing value.
+    pub fn get(&self) -> &T {
+        // unsafe: index was resolved from the map by Table::entry and is valid.
+        unsafe { self.table.data.get_unchecked(self.index) }
+    }
+
+    /// Get a mutable reference to the existing value.
+    pub fn get_mut(&mut self) -> &mut T {
+        // unsafe: index was resolved from the map by Table::entry and is valid.
+        unsafe { self.table.data.get_unchecked_mut(self.index) }
+    }
+
+    /// Turn the entry into a mutable reference tied to the table's lifetime.
+    pub fn into_mut(self) -> &'a mut T {
+        // unsafe: index was resolved from the map by Table::entry and is valid.
+        unsafe { self.table.data.get_unchecked_mut(self.index) }
+    }
+
+    /// Remove the entry, reusing the already-resolved dense index instead of
+    /// looking the value up again.
+    pub fn remove(self) -> T {
+        let key = self.table.reverse[self.index];
+        self.table.map.remove(&key);
+        self.table.remove_at(self.index)
+    }
+}
+
+/// A vacant entry. See [`Table::entry`].
+pub struct VacantEntry<'a, T, G = DefaultKeyGen> {
+    table: &'a mut Table<T, G>,
+    key: u128,
+}
+
+impl<'a, T, G> VacantEntry<'a, T, G> {
+    /// Insert `value` for this entry's key, returning a mutable reference to it.
+    pub fn insert(self, value: T) -> &'a mut T {
+        self.table.data.push(value);
+        self.table.reverse.push(self.key);
+        let index = self.table.data.len() - 1;
+        self.table.map.insert(self.key, index);
+        // unsafe: index was just pushed to data and is valid.
+        unsafe { self.table.data.get_unchecked_mut(index) }
+    }
+}
+
+// Serialized as a plain sequence of (key, value) pairs: `map` is redundant, it is just
+// an index over `data`/`reverse` and gets rebuilt on load via `add_with_key`.
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, G> serde::Serialize for Table<T, G> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, G: Default> serde::Deserialize<'de> for Table<T, G> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pairs = Vec::<(u128, T)>::deserialize(deserializer)?;
+        let mut table = Table::with_capacity(pairs.len());
+        for (key, value) in pairs {
+            table.add_with_key(key, value);
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T: borsh::BorshSerialize, G> borsh::BorshSerialize for Table<T, G> {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let pairs: Vec<(u128, &T)> = self.iter().collect();
+        pairs.serialize(writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T: borsh::BorshDeserialize, G: Default> borsh::BorshDeserialize for Table<T, G> {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let pairs = Vec::<(u128, T)>::deserialize_reader(reader)?;
+        let mut table = Table::with_capacity(pairs.len());
+        for (key, value) in pairs {
+            table.add_with_key(key, value);
+        }
+        Ok(table)
+    }
 }
 
 #[cfg(test)]
@@ -181,6 +495,84 @@ mod tests {
         assert_eq!(table.count(), 1);
     }
 
+    #[test]
+    fn test_retain() {
+        let mut table: Table<i32> = Table::default();
+        let key1 = table.add(1);
+        let key2 = table.add(2);
+        let key3 = table.add(3);
+        table.retain(|_, v| *v % 2 == 1);
+        assert_eq!(table.count(), 2);
+        assert_eq!(table.get(key1), Some(&1));
+        assert_eq!(table.get(key2), None);
+        assert_eq!(table.get(key3), Some(&3));
+        // map must still be consistent after the rebuild.
+        let mut remaining: Vec<_> = table.iter().map(|(_, v)| *v).collect();
+        remaining.sort();
+        assert_eq!(remaining, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut table: Table<i32> = Table::default();
+        let key1 = table.add(1);
+        let key2 = table.add(2);
+        let mut drained: Vec<_> = table.drain().collect();
+        drained.sort_by_key(|(_, v)| *v);
+        assert_eq!(drained, vec![(key1, 1), (key2, 2)]);
+        assert_eq!(table.count(), 0);
+        assert_eq!(table.get(key1), None);
+    }
+
+    #[test]
+    fn test_clone_does_not_duplicate_key_stream() {
+        let mut table: Table<i32> = Table::default();
+        let mut clone = table.clone();
+        // if the clone's RNG state were copied verbatim from `table`, both would draw the
+        // same "random" key next and collide.
+        assert_ne!(table.add(1), clone.add(1));
+    }
+
+    #[test]
+    fn test_custom_key_gen() {
+        struct CounterKeyGen(u128);
+        impl KeyGen for CounterKeyGen {
+            fn next_key(&mut self) -> u128 {
+                self.0 += 1;
+                self.0
+            }
+        }
+
+        let mut table: Table<i32, CounterKeyGen> = Table::with_key_gen(4, CounterKeyGen(0));
+        let key1 = table.add(10);
+        let key2 = table.add(20);
+        assert_eq!(key1, 1);
+        assert_eq!(key2, 2);
+        assert_eq!(table.get(key1), Some(&10));
+        assert_eq!(table.get(key2), Some(&20));
+    }
+
+    #[test]
+    fn test_sharded_table_add_get_remove() {
+        let table: ShardedTable<i32> = ShardedTable::with_shards(4);
+        let key = table.add(42);
+        assert_eq!(*table.get(key).unwrap(), 42);
+        *table.get_mut(key).unwrap() = 43;
+        assert_eq!(*table.get(key).unwrap(), 43);
+        assert_eq!(table.remove(key), Some(43));
+        assert!(table.get(key).is_none());
+    }
+
+    #[test]
+    fn test_sharded_table_par_values_mut() {
+        let table: ShardedTable<i32> = ShardedTable::with_shards(4);
+        let keys: Vec<_> = (0..100).map(|i| table.add(i)).collect();
+        table.par_values_mut(|v| *v += 1);
+        for (i, key) in keys.into_iter().enumerate() {
+            assert_eq!(*table.get(key).unwrap(), i as i32 + 1);
+        }
+    }
+
     #[test]
     fn test_values() {
         let mut table: Table<i32> = Table::default(); // Specify type for empty_table
@@ -190,6 +582,121 @@ mod tests {
         assert_eq!(values, vec![&42, &24]);
     }
 
+    #[test]
+    fn test_values_mut() {
+        let mut table: Table<i32> = Table::default();
+        table.add(42);
+        table.add(24);
+        table.values_mut().for_each(|v| *v += 1);
+        let values: Vec<_> = table.values().collect();
+        assert_eq!(values, vec![&43, &25]);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut table: Table<i32> = Table::default();
+        let key1 = table.add(42);
+        let key2 = table.add(24);
+        let pairs: Vec<_> = table.iter().collect();
+        assert_eq!(pairs, vec![(key1, &42), (key2, &24)]);
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut table: Table<i32> = Table::default();
+        let key1 = table.add(42);
+        let key2 = table.add(24);
+        table.iter_mut().for_each(|(_, v)| *v += 1);
+        assert_eq!(table.get(key1), Some(&43));
+        assert_eq!(table.get(key2), Some(&25));
+    }
+
+    #[test]
+    fn test_get_by_index() {
+        let mut table: Table<i32> = Table::default();
+        let key1 = table.add(42);
+        let key2 = table.add(24);
+        assert_eq!(table.get_by_index(0), Some((key1, &42)));
+        assert_eq!(table.get_by_index(1), Some((key2, &24)));
+        assert_eq!(table.get_by_index(2), None);
+        assert_eq!(table.key_at(0), Some(key1));
+        assert_eq!(table.key_at(2), None);
+    }
+
+    #[test]
+    fn test_get_by_index_mut() {
+        let mut table: Table<i32> = Table::default();
+        let key = table.add(42);
+        if let Some((found_key, value)) = table.get_by_index_mut(0) {
+            assert_eq!(found_key, key);
+            *value += 1;
+        } else {
+            panic!("expected an entry at index 0");
+        }
+        assert_eq!(table.get(key), Some(&43));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut table: Table<i32> = Table::default();
+        let key1 = table.add(42);
+        let key2 = table.add(24);
+        let json = serde_json::to_string(&table).unwrap();
+        let restored: Table<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get(key1), Some(&42));
+        assert_eq!(restored.get(key2), Some(&24));
+        assert_eq!(restored.count(), 2);
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_round_trip() {
+        let mut table: Table<i32> = Table::default();
+        let key1 = table.add(42);
+        let key2 = table.add(24);
+        let bytes = borsh::to_vec(&table).unwrap();
+        let restored: Table<i32> = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(restored.get(key1), Some(&42));
+        assert_eq!(restored.get(key2), Some(&24));
+        assert_eq!(restored.count(), 2);
+    }
+
+    #[test]
+    fn test_join_with() {
+        struct A(f32);
+        struct B(f32, u128);
+
+        let mut a_table: Table<A> = Table::default();
+        let mut b_table: Table<B> = Table::default();
+        let a_key1 = a_table.add(A(1.0));
+        let a_key2 = a_table.add(A(2.0));
+        b_table.add(B(10.0, a_key1));
+        // foreign key pointing at nothing; should be skipped.
+        b_table.add(B(20.0, 999));
+
+        let joined: Vec<_> = b_table.join_with(&a_table, |b| b.1).collect();
+        assert_eq!(joined.len(), 1);
+        assert_eq!(joined[0].0 .0, 10.0);
+        assert_eq!(joined[0].1 .0, 1.0);
+        let _ = a_key2;
+    }
+
+    #[test]
+    fn test_join_with_mut() {
+        struct A(f32);
+        struct B(f32, u128);
+
+        let mut a_table: Table<A> = Table::default();
+        let mut b_table: Table<B> = Table::default();
+        let a_key = a_table.add(A(1.0));
+        b_table.add(B(10.0, a_key));
+        b_table.add(B(20.0, 999));
+
+        b_table.join_with_mut(&mut a_table, |b| b.1, |b, a| a.0 += b.0);
+        assert_eq!(a_table.get(a_key).unwrap().0, 11.0);
+    }
+
     #[test]
     fn test_edge_cases() {
         let mut table: Table<i32> = Table::default(); // Specify type for empty_table
@@ -217,4 +724,56 @@ mod tests {
         let empty_table: Table<i32> = Table::default(); // Specify type for empty_table
         assert_eq!(empty_table.count(), 0);
     }
+
+    #[test]
+    fn test_entry_or_insert_vacant() {
+        let mut table: Table<i32> = Table::default();
+        let key = 1;
+        *table.entry(key).or_insert(0) += 1;
+        assert_eq!(table.get(key), Some(&1));
+    }
+
+    #[test]
+    fn test_entry_or_insert_occupied() {
+        let mut table: Table<i32> = Table::default();
+        let key = table.add(41);
+        *table.entry(key).or_insert(0) += 1;
+        assert_eq!(table.get(key), Some(&42));
+    }
+
+    #[test]
+    fn test_entry_and_modify() {
+        let mut table: Table<i32> = Table::default();
+        let key = table.add(1);
+        table.entry(key).and_modify(|v| *v += 1).or_insert(100);
+        assert_eq!(table.get(key), Some(&2));
+
+        table.entry(999).and_modify(|v| *v += 1).or_insert(100);
+        assert_eq!(table.get(999), Some(&100));
+    }
+
+    #[test]
+    fn test_entry_occupied_remove() {
+        let mut table: Table<i32> = Table::default();
+        let key = table.add(42);
+        match table.entry(key) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove(), 42),
+            Entry::Vacant(_) => panic!("expected occupied entry"),
+        }
+        assert_eq!(table.get(key), None);
+    }
+
+    #[test]
+    fn test_add_with_key_overwrites_in_place() {
+        let mut table: Table<i32> = Table::default();
+        let key = table.add(1);
+        let other = table.add(2);
+        table.add_with_key(key, 3);
+        assert_eq!(table.get(key), Some(&3));
+        // the other entry's dense index must be unaffected by the overwrite.
+        assert_eq!(table.get(other), Some(&2));
+        assert_eq!(table.key_at(0), Some(key));
+        assert_eq!(table.key_at(1), Some(other));
+        assert_eq!(table.count(), 2);
+    }
 }
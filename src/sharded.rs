@@ -0,0 +1,135 @@
+//! A sharded, concurrency-friendly wrapper around [`Table`] for parallel ECS systems.
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{DefaultKeyGen, KeyGen, Table};
+
+/// Number of shards a [`ShardedTable`] partitions across when created with [`ShardedTable::new`].
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// A table that partitions entries across several [`Table`] shards, each behind its own
+/// `RwLock`, so that systems touching disjoint keys can run in parallel instead of
+/// contending on one global lock.
+///
+/// Shard selection just takes the key's low 64 bits modulo the shard count: keys are already uniformly
+/// random `u128`s, so no extra hashing is needed to keep load balanced across shards.
+pub struct ShardedTable<T> {
+    shards: Vec<RwLock<Table<T>>>,
+    // Which shard a key lands in can't be decided until the key itself is drawn, so key
+    // generation can't simply delegate to one shard's own `Table::add`; instead a single
+    // generator is shared across shards, still only seeded once like `DefaultKeyGen`.
+    key_gen: Mutex<DefaultKeyGen>,
+}
+
+impl<T> Default for ShardedTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ShardedTable<T> {
+    /// Create a sharded table with the default number of shards.
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARD_COUNT)
+    }
+
+    /// Create a sharded table with a specific number of shards.
+    pub fn with_shards(shard_count: usize) -> Self {
+        assert!(shard_count > 0, "ShardedTable requires at least one shard");
+        Self {
+            shards: (0..shard_count).map(|_| RwLock::new(Table::default())).collect(),
+            key_gen: Mutex::new(DefaultKeyGen::default()),
+        }
+    }
+
+    fn shard_for(&self, key: u128) -> &RwLock<Table<T>> {
+        let index = (key as usize) % self.shards.len();
+        &self.shards[index]
+    }
+
+    /// Add a new value with random key. Besides briefly locking the shared key generator,
+    /// locks only the shard the key falls into.
+    pub fn add(&self, value: T) -> u128 {
+        let key = self.key_gen.lock().unwrap().next_key();
+        self.add_with_key(key, value);
+        key
+    }
+
+    /// Add a new value with a manual key. Locks only the shard the key falls into.
+    pub fn add_with_key(&self, key: u128, value: T) {
+        self.shard_for(key).write().unwrap().add_with_key(key, value);
+    }
+
+    /// Get a value by key. Locks only the shard the key falls into.
+    pub fn get(&self, key: u128) -> Option<ShardedRef<'_, T>> {
+        let guard = self.shard_for(key).read().unwrap();
+        let index = guard.index_of(key)?;
+        Some(ShardedRef { guard, index })
+    }
+
+    /// Get a mutable value by key. Locks only the shard the key falls into.
+    pub fn get_mut(&self, key: u128) -> Option<ShardedRefMut<'_, T>> {
+        let guard = self.shard_for(key).write().unwrap();
+        let index = guard.index_of(key)?;
+        Some(ShardedRefMut { guard, index })
+    }
+
+    /// Remove an element using it's key. Locks only the shard the key falls into.
+    pub fn remove(&self, key: u128) -> Option<T> {
+        self.shard_for(key).write().unwrap().remove(key)
+    }
+
+    /// Run `f` over every shard's values on a separate thread, scaling iteration-heavy
+    /// systems across cores. Blocks until every shard has been processed.
+    pub fn par_values_mut(&self, f: impl Fn(&mut T) + Sync)
+    where
+        T: Send + Sync,
+    {
+        std::thread::scope(|scope| {
+            for shard in &self.shards {
+                let f = &f;
+                scope.spawn(move || {
+                    shard.write().unwrap().values_mut().for_each(f);
+                });
+            }
+        });
+    }
+}
+
+/// A read guard over a single value in a [`ShardedTable`]. See [`ShardedTable::get`].
+pub struct ShardedRef<'a, T> {
+    guard: RwLockReadGuard<'a, Table<T>>,
+    index: usize,
+}
+
+impl<T> Deref for ShardedRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // unwrap: the index was resolved when this guard was created, and the shard lock
+        // has been held continuously since, so nothing could have shifted the dense vec.
+        self.guard.get_by_index(self.index).unwrap().1
+    }
+}
+
+/// A write guard over a single value in a [`ShardedTable`]. See [`ShardedTable::get_mut`].
+pub struct ShardedRefMut<'a, T> {
+    guard: RwLockWriteGuard<'a, Table<T>>,
+    index: usize,
+}
+
+impl<T> Deref for ShardedRefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // unwrap: see ShardedRef::deref.
+        self.guard.get_by_index(self.index).unwrap().1
+    }
+}
+
+impl<T> DerefMut for ShardedRefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // unwrap: see ShardedRef::deref.
+        self.guard.get_by_index_mut(self.index).unwrap().1
+    }
+}